@@ -1,23 +1,24 @@
 #![allow(warnings)]
+use std::collections::HashSet;
 use std::default;
 use std::fs::File;
 use std::io::{self, Read, Write};
 use std::net::IpAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
+use arboard::Clipboard;
 use base64::encode;
 use clap::Parser;
-use clipboard::ClipboardContext;
-use clipboard::ClipboardProvider;
 use futures_util::stream::{Stream, StreamExt};
 use local_ip_address::local_ip;
 use notify::Watcher;
-use pulldown_cmark::{html, CowStr, Event, Options, Parser as MdParser};
+use percent_encoding::percent_decode_str;
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser as MdParser, Tag, TagEnd};
 use tokio::sync::{broadcast, RwLock};
-use warp::{sse, Filter};
+use warp::{sse, Filter, Reply};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -37,6 +38,21 @@ struct Args {
     //Renders the markdown in clipboard
     #[arg(short = 'C', long = "clipboard")]
     clipboard: bool,
+    //Inline every local/remote asset the rendered HTML references, producing one portable file (static mode only)
+    #[arg(short = 'e', long = "embed", alias = "self-contained")]
+    embed: bool,
+    //Don't fetch http(s) assets while embedding; leave their original URL in place
+    #[arg(long = "no-fetch", requires = "embed")]
+    no_fetch: bool,
+    //Render the markdown and copy the resulting HTML (with a plain-text fallback) to the clipboard
+    #[arg(long = "copy-html")]
+    copy_html: bool,
+    //Custom document template with {{ title }}, {{ content }}, {{ styles }} and {{ reload_script }} placeholders
+    #[arg(long = "template", value_name = "FILE")]
+    template: Option<PathBuf>,
+    //CSS theme to use: a bundled theme name (light, dark, print) or a path to an external .css file
+    #[arg(long = "theme", value_name = "NAME_OR_FILE")]
+    theme: Option<String>,
 }
 
 #[tokio::main]
@@ -50,7 +66,9 @@ async fn main() -> io::Result<()> {
         std::process::exit(1);
     }
 
-    if args.static_mode {
+    if args.copy_html {
+        run_copy_html_mode(&args)?;
+    } else if args.static_mode {
         run_static_mode(&args)?;
     } else {
         run_server_mode(&args).await?;
@@ -59,14 +77,16 @@ async fn main() -> io::Result<()> {
     Ok(())
 }
 
-fn run_static_mode(args: &Args) -> io::Result<()> {
-    let (file_name, markdown_input) = if args.clipboard {
-        let mut clipboard: ClipboardContext = ClipboardProvider::new().unwrap();
-        let content = clipboard.get_contents().unwrap_or_else(|err| {
+// Shared by static mode and copy-html mode: resolves the markdown source from
+// the clipboard, a file, or stdin, in that order of precedence.
+fn gather_markdown_input(args: &Args) -> io::Result<(String, String)> {
+    if args.clipboard {
+        let mut clipboard = Clipboard::new().unwrap();
+        let content = clipboard.get_text().unwrap_or_else(|err| {
             eprintln!("Error reading from clipboard: {}", err);
             std::process::exit(1);
         });
-        (String::from("Clipboard"), content)
+        Ok((String::from("Clipboard"), content))
     } else {
         match &args.file {
             Some(file_path) => {
@@ -76,23 +96,70 @@ fn run_static_mode(args: &Args) -> io::Result<()> {
                 });
                 let mut content = String::new();
                 file.read_to_string(&mut content)?;
-                (
+                Ok((
                     file_path.file_name().unwrap().to_string_lossy().to_string(),
                     content,
-                )
+                ))
             }
             None => {
                 let mut content = String::new();
                 io::stdin().read_to_string(&mut content)?;
-                (String::from("New file"), content)
+                Ok((String::from("New file"), content))
             }
         }
-    };
+    }
+}
+
+fn run_copy_html_mode(args: &Args) -> io::Result<()> {
+    let (_, markdown_input) = gather_markdown_input(args)?;
+    let html_output = render_markdown_to_html(&markdown_input, syntect_theme_name(&args.theme));
+
+    let mut clipboard = Clipboard::new().unwrap();
+    clipboard
+        .set_html(html_output.as_str(), Some(markdown_input.as_str()))
+        .unwrap_or_else(|err| {
+            eprintln!("Error copying HTML to clipboard: {}", err);
+            std::process::exit(1);
+        });
 
-    let html_output = render_markdown_to_html(&markdown_input);
-    let style = read_style_css();
+    println!("Rendered HTML copied to clipboard.");
+
+    Ok(())
+}
+
+fn run_static_mode(args: &Args) -> io::Result<()> {
+    let (file_name, markdown_input) = gather_markdown_input(args)?;
+
+    let html_output = render_markdown_to_html(&markdown_input, syntect_theme_name(&args.theme));
+    let style = resolve_theme_css(&args.theme);
     let fonts = read_fonts();
-    let html_content = build_full_html(&file_name, &html_output, &style, &fonts, false);
+    let template = load_custom_template(&args.template);
+    let mut html_content = build_full_html(
+        &file_name,
+        &html_output,
+        &style,
+        &fonts,
+        false,
+        "",
+        "",
+        template.as_deref(),
+    );
+
+    if args.embed {
+        let base_dir = match &args.file {
+            Some(file_path) => file_path
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| PathBuf::from(".")),
+            None => std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        };
+        // embed_assets may call reqwest::blocking for http(s) assets; block_in_place
+        // hands this worker thread's other tasks off so that nested blocking is allowed.
+        html_content = tokio::task::block_in_place(|| {
+            embed_assets(&html_content, &base_dir, !args.no_fetch)
+        });
+    }
 
     let temp_file = tempfile::Builder::new()
         .prefix("markdown_preview_")
@@ -155,27 +222,38 @@ fn open_in_browser(link: String) {
 
 type EventStream = Pin<Box<dyn Stream<Item = Result<sse::Event, warp::Error>> + Send>>;
 
-fn event_stream(rx: broadcast::Receiver<()>) -> EventStream {
+// Only forwards a reload to clients currently viewing the file that changed.
+fn event_stream(rx: broadcast::Receiver<PathBuf>, client_path: PathBuf) -> EventStream {
     let stream = async_stream::stream! {
         let mut rx = rx;
-        while let Ok(_) = rx.recv().await {
-            yield Ok(sse::Event::default().data("reload"));
+        while let Ok(changed_path) = rx.recv().await {
+            if changed_path == client_path {
+                yield Ok(sse::Event::default().data("reload"));
+            }
         }
     };
     Box::pin(stream)
 }
 
+#[derive(serde::Deserialize)]
+struct EventsQuery {
+    path: Option<String>,
+}
+
 async fn run_server_mode(args: &Args) -> io::Result<()> {
-    let (file_path, file_name, markdown_input) = if args.clipboard {
-        let mut clipboard: ClipboardContext = ClipboardProvider::new().unwrap();
-        let content = clipboard.get_contents().unwrap_or_else(|err| {
+    let (root_path, is_directory, file_name, initial_rel_path, initial_markdown) = if args.clipboard
+    {
+        let mut clipboard = Clipboard::new().unwrap();
+        let content = clipboard.get_text().unwrap_or_else(|err| {
             eprintln!("Error reading from clipboard: {}", err);
             std::process::exit(1);
         });
         (
-            PathBuf::from("Clipboard"),
+            PathBuf::from("."),
+            false,
             String::from("Clipboard"),
-            content,
+            PathBuf::from("Clipboard"),
+            Some(content),
         )
     } else {
         let file_path = match &args.file {
@@ -185,20 +263,61 @@ async fn run_server_mode(args: &Args) -> io::Result<()> {
                 std::process::exit(1);
             }
         };
-        let file_name = file_path.file_name().unwrap().to_string_lossy().to_string();
-        let markdown_input = read_markdown_input(&file_path)?;
-        (file_path, file_name, markdown_input)
+
+        if file_path.is_dir() {
+            let dir_name = file_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| file_path.to_string_lossy().to_string());
+            (file_path, true, dir_name, PathBuf::new(), None)
+        } else {
+            let file_name = file_path.file_name().unwrap().to_string_lossy().to_string();
+            let markdown_input = read_markdown_input(&file_path)?;
+            let root_path = file_path
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| PathBuf::from("."));
+            (
+                root_path,
+                false,
+                file_name.clone(),
+                PathBuf::from(file_name),
+                Some(markdown_input),
+            )
+        }
     };
 
-    let html_output = render_markdown_to_html(&markdown_input);
-    let style = read_style_css();
+    let style = resolve_theme_css(&args.theme);
     let fonts = read_fonts();
-    let (tx, _) = broadcast::channel::<()>(100);
+    let custom_template = load_custom_template(&args.template);
+    let highlight_theme = syntect_theme_name(&args.theme).to_string();
+    let (tx, _) = broadcast::channel::<PathBuf>(100);
+
+    let html_cache = Arc::new(RwLock::new(std::collections::HashMap::new()));
+    if let Some(markdown_input) = initial_markdown {
+        let html_output = render_markdown_to_html(&markdown_input, &highlight_theme);
+        html_cache
+            .write()
+            .await
+            .insert(initial_rel_path.clone(), html_output);
+    }
+
+    let files = if is_directory {
+        build_file_index(&root_path)
+    } else {
+        vec![initial_rel_path]
+    };
+
     let app_state = Arc::new(AppState {
-        html_content: Arc::new(RwLock::new(html_output)),
+        html_cache,
         css_content: style,
         fonts,
-        file_path: file_path.clone(),
+        custom_template,
+        highlight_theme,
+        root_path: root_path.clone(),
+        is_directory,
+        files: Arc::new(RwLock::new(files)),
         notifier: tx.clone(),
         file_name,
     });
@@ -213,11 +332,25 @@ async fn run_server_mode(args: &Args) -> io::Result<()> {
         .and(state_filter.clone())
         .and_then(serve_html);
 
+    let view_route = warp::path("view")
+        .and(warp::path::tail())
+        .and(warp::header::optional::<String>("range"))
+        .and(state_filter.clone())
+        .and_then(serve_view);
+
     let sse_route = warp::path("events")
         .and(warp::get())
+        .and(warp::query::<EventsQuery>())
         .and(state_filter.clone())
         .and_then(sse_handler);
 
+    // Fallback for relative image/video references resolved against the root directory,
+    // e.g. `![](diagram.png)` served straight from disk with HTTP Range support.
+    let asset_route = warp::path::full()
+        .and(warp::header::optional::<String>("range"))
+        .and(state_filter.clone())
+        .and_then(serve_asset_fallback);
+
     let mut host = args.host.clone();
     if args.host == "0.0.0.0" {
         if let Ok(local_ip_address) = local_ip() {
@@ -233,9 +366,14 @@ async fn run_server_mode(args: &Args) -> io::Result<()> {
         .parse()
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
 
-    warp::serve(html_route.or(sse_route))
-        .run((address, args.port))
-        .await;
+    warp::serve(
+        html_route
+            .or(view_route)
+            .or(sse_route)
+            .or(asset_route),
+    )
+    .run((address, args.port))
+    .await;
     Ok(())
 }
 
@@ -246,38 +384,154 @@ fn read_markdown_input(file_path: &PathBuf) -> io::Result<String> {
     Ok(content)
 }
 
-fn render_markdown_to_html(markdown_input: &str) -> String {
+// Building a SyntaxSet/ThemeSet parses syntect's bundled dumps, which is too slow to
+// redo on every render (e.g. every file-watch save in server mode), so each is built
+// once per process and reused.
+static SYNTAX_SET: OnceLock<syntect::parsing::SyntaxSet> = OnceLock::new();
+static THEME_SET: OnceLock<syntect::highlighting::ThemeSet> = OnceLock::new();
+
+fn render_markdown_to_html(markdown_input: &str, highlight_theme: &str) -> String {
     let mut options = Options::all();
 
     let parser = MdParser::new_ext(&markdown_input, options);
     let mut html_output = String::new();
-    html::push_html(
-        &mut html_output,
-        parser.map(|event| match event {
-            Event::SoftBreak => Event::Html("<br>".into()),
-            Event::InlineMath(s) => {
-                let mut str = String::from("<span class=\"math math-inline\">$");
-                str.push_str(&s.into_string());
-                str.push_str("$</span>");
-                Event::Html(CowStr::from(str))
-            }
-            Event::DisplayMath(s) => {
-                let mut str = String::from("<span class=\"math math-display\">$$");
-                str.push_str(&s.into_string());
-                str.push_str("$$</span>");
-                Event::Html(CowStr::from(str))
-            }
-            _ => event,
-        }),
-    );
+
+    let syntax_set = SYNTAX_SET.get_or_init(syntect::parsing::SyntaxSet::load_defaults_newlines);
+    let theme_set = THEME_SET.get_or_init(syntect::highlighting::ThemeSet::load_defaults);
+    let theme = theme_set
+        .themes
+        .get(highlight_theme)
+        .or_else(|| theme_set.themes.get("base16-ocean.light"))
+        .expect("syntect bundles base16-ocean.light");
+
+    let mut code_block_lang: Option<String> = None;
+    let mut code_buffer = String::new();
+
+    let events = parser.filter_map(|event| match event {
+        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang))) => {
+            code_block_lang = Some(lang.into_string());
+            code_buffer.clear();
+            None
+        }
+        Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) => {
+            code_block_lang = Some(String::new());
+            code_buffer.clear();
+            None
+        }
+        Event::Text(text) if code_block_lang.is_some() => {
+            code_buffer.push_str(&text);
+            None
+        }
+        Event::End(TagEnd::CodeBlock) if code_block_lang.is_some() => {
+            let lang = code_block_lang.take().unwrap_or_default();
+            let highlighted = highlight_code_block(&code_buffer, &lang, &syntax_set, theme);
+            Some(Event::Html(CowStr::from(highlighted)))
+        }
+        Event::SoftBreak => Some(Event::Html("<br>".into())),
+        Event::InlineMath(s) => {
+            let mut str = String::from("<span class=\"math math-inline\">$");
+            str.push_str(&s.into_string());
+            str.push_str("$</span>");
+            Some(Event::Html(CowStr::from(str)))
+        }
+        Event::DisplayMath(s) => {
+            let mut str = String::from("<span class=\"math math-display\">$$");
+            str.push_str(&s.into_string());
+            str.push_str("$$</span>");
+            Some(Event::Html(CowStr::from(str)))
+        }
+        other => Some(other),
+    });
+
+    html::push_html(&mut html_output, events);
     html_output
 }
 
+// Highlights a fenced code block's contents against `lang`, falling back to escaped
+// plain text (via syntect's plain-text syntax) when the language isn't recognized.
+fn highlight_code_block(
+    code: &str,
+    lang: &str,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+) -> String {
+    // Fence info strings can carry extra annotations (e.g. "rust,ignore" or "rust ignore"),
+    // but find_syntax_by_token only knows the language name itself.
+    let lang_token = lang.split([',', ' ', '\t']).next().unwrap_or(lang);
+    let syntax = syntax_set
+        .find_syntax_by_token(lang_token)
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+    syntect::html::highlighted_html_for_string(code, syntax_set, syntax, theme)
+        .unwrap_or_else(|_| format!("<pre><code>{}</code></pre>", html_escape(code)))
+}
+
+fn syntect_theme_name(theme: &Option<String>) -> &'static str {
+    match theme.as_deref() {
+        Some("dark") => "base16-ocean.dark",
+        Some("print") => "InspiredGitHub",
+        _ => "base16-ocean.light",
+    }
+}
+
 fn read_style_css() -> String {
     let css_file = include_str!("../src/style.css").to_string();
     css_file
 }
 
+fn bundled_theme_css(name: &str) -> Option<&'static str> {
+    match name.to_ascii_lowercase().as_str() {
+        "light" => Some(include_str!("./themes/light.css")),
+        "dark" => Some(include_str!("./themes/dark.css")),
+        "print" => Some(include_str!("./themes/print.css")),
+        _ => None,
+    }
+}
+
+// Resolves `--theme`: a path to an external stylesheet, one of the bundled theme
+// names, or (when absent/unrecognized) the built-in stylesheet.
+fn resolve_theme_css(theme: &Option<String>) -> String {
+    let Some(value) = theme else {
+        return read_style_css();
+    };
+
+    let path = PathBuf::from(value);
+    if path.is_file() {
+        std::fs::read_to_string(&path).unwrap_or_else(|err| {
+            eprintln!("Error reading theme file {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    } else if let Some(css) = bundled_theme_css(value) {
+        css.to_string()
+    } else {
+        eprintln!(
+            "Warning: unknown theme '{}', falling back to the default theme.",
+            value
+        );
+        read_style_css()
+    }
+}
+
+fn load_custom_template(template_path: &Option<PathBuf>) -> Option<String> {
+    template_path.as_ref().map(|path| {
+        std::fs::read_to_string(path).unwrap_or_else(|err| {
+            eprintln!("Error reading template file {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    })
+}
+
+// Minimal `{{ key }}` substitution (tolerating `{{key}}` too); no loops or
+// conditionals are needed for the handful of placeholders `--template` supports.
+fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut output = template.to_string();
+    for (key, value) in vars {
+        output = output.replace(&format!("{{{{ {} }}}}", key), value);
+        output = output.replace(&format!("{{{{{}}}}}", key), value);
+    }
+    output
+}
+
 struct Fonts {
     font_regular: String,
     font_medium: String,
@@ -295,16 +549,20 @@ fn read_fonts() -> Fonts {
 }
 
 struct AppState {
-    html_content: Arc<RwLock<String>>,
+    html_cache: Arc<RwLock<std::collections::HashMap<PathBuf, String>>>,
     css_content: String,
     fonts: Fonts,
-    file_path: PathBuf,
-    notifier: broadcast::Sender<()>,
+    custom_template: Option<String>,
+    highlight_theme: String,
+    root_path: PathBuf,
+    is_directory: bool,
+    files: Arc<RwLock<Vec<PathBuf>>>,
+    notifier: broadcast::Sender<PathBuf>,
     file_name: String,
 }
 
 fn watch_markdown_file(app_state: Arc<AppState>) {
-    if app_state.file_path.to_string_lossy() == "Clipboard" {
+    if !app_state.is_directory && app_state.file_name == "Clipboard" {
         return; // Disable watcher for clipboard input
     }
 
@@ -316,6 +574,12 @@ fn watch_markdown_file(app_state: Arc<AppState>) {
         RecommendedWatcher(RecommendedWatcher),
     }
 
+    let recursive_mode = if app_state.is_directory {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+
     let (tx_notify, rx_notify) = channel();
     let watcher = if cfg!(target_os = "linux") {
         let mut watcher = PollWatcher::new(
@@ -324,13 +588,13 @@ fn watch_markdown_file(app_state: Arc<AppState>) {
         )
         .unwrap();
         watcher
-            .watch(app_state.file_path.as_path(), RecursiveMode::NonRecursive)
+            .watch(app_state.root_path.as_path(), recursive_mode)
             .unwrap();
         WatcherType::PollWatcher(watcher)
     } else {
         let mut watcher = RecommendedWatcher::new(tx_notify, Config::default()).unwrap();
         watcher
-            .watch(app_state.file_path.as_path(), RecursiveMode::NonRecursive)
+            .watch(app_state.root_path.as_path(), recursive_mode)
             .unwrap();
         WatcherType::RecommendedWatcher(watcher)
     };
@@ -338,23 +602,64 @@ fn watch_markdown_file(app_state: Arc<AppState>) {
     for res in rx_notify {
         match res {
             Ok(event) => {
-                if let EventKind::Modify(_) = event.kind {
-                    println!("File changed, updating content...");
-                    match std::fs::read_to_string(&app_state.file_path) {
-                        Ok(markdown_input) => {
-                            let html_output = render_markdown_to_html(&markdown_input);
-                            let app_state_clone = app_state.clone();
+                let is_relevant = matches!(
+                    event.kind,
+                    EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                );
+                if !is_relevant {
+                    continue;
+                }
+
+                for changed_path in &event.paths {
+                    if !is_markdown_file(changed_path) {
+                        continue;
+                    }
+                    let Ok(rel_path) = changed_path.strip_prefix(&app_state.root_path) else {
+                        continue;
+                    };
+                    let rel_path = rel_path.to_path_buf();
+
+                    println!("{} changed, updating content...", rel_path.display());
+
+                    let app_state_clone = app_state.clone();
+                    match event.kind {
+                        EventKind::Remove(_) => {
                             tokio::spawn(async move {
-                                let mut html_content = app_state_clone.html_content.write().await;
-                                *html_content = html_output;
-                                if let Err(e) = app_state_clone.notifier.send(()) {
+                                app_state_clone.html_cache.write().await.remove(&rel_path);
+                                app_state_clone.files.write().await.retain(|p| p != &rel_path);
+                                if let Err(e) = app_state_clone.notifier.send(rel_path) {
                                     eprintln!("Error sending notification: {}", e);
                                 }
                             });
                         }
-                        Err(e) => {
-                            eprintln!("Error reading file: {}", e);
-                        }
+                        _ => match std::fs::read_to_string(changed_path) {
+                            Ok(markdown_input) => {
+                                let html_output = render_markdown_to_html(
+                                    &markdown_input,
+                                    &app_state_clone.highlight_theme,
+                                );
+                                tokio::spawn(async move {
+                                    app_state_clone
+                                        .html_cache
+                                        .write()
+                                        .await
+                                        .insert(rel_path.clone(), html_output);
+                                    {
+                                        let mut files = app_state_clone.files.write().await;
+                                        if !files.contains(&rel_path) {
+                                            files.push(rel_path.clone());
+                                            files.sort();
+                                        }
+                                    }
+                                    if let Err(e) = app_state_clone.notifier.send(rel_path) {
+                                        eprintln!("Error sending notification: {}", e);
+                                    }
+                                });
+                            }
+                            Err(e) => {
+                                eprintln!("Error reading file: {}", e);
+                            }
+                        },
                     }
                 }
             }
@@ -365,22 +670,290 @@ fn watch_markdown_file(app_state: Arc<AppState>) {
     }
 }
 
-async fn sse_handler(app_state: Arc<AppState>) -> Result<impl warp::Reply, warp::Rejection> {
+async fn sse_handler(
+    query: EventsQuery,
+    app_state: Arc<AppState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let client_path = match query.path {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(&app_state.file_name),
+    };
     let rx = app_state.notifier.subscribe();
-    let stream = event_stream(rx);
+    let stream = event_stream(rx, client_path);
     Ok(warp::sse::reply(warp::sse::keep_alive().stream(stream)))
 }
 
 async fn serve_html(app_state: Arc<AppState>) -> Result<impl warp::Reply, warp::Rejection> {
-    let html_content = app_state.html_content.read().await;
+    if app_state.is_directory {
+        let files = app_state.files.read().await;
+        let sidebar = build_sidebar_html(&files, None);
+        let placeholder = "<p class=\"omd-placeholder\">Select a file from the sidebar to preview it.</p>";
+        let full_html = build_full_html(
+            &app_state.file_name,
+            placeholder,
+            &app_state.css_content,
+            &app_state.fonts,
+            false,
+            &sidebar,
+            "",
+            app_state.custom_template.as_deref(),
+        );
+        Ok(warp::reply::html(full_html))
+    } else {
+        let rel_path = PathBuf::from(&app_state.file_name);
+        let html_content = app_state
+            .html_cache
+            .read()
+            .await
+            .get(&rel_path)
+            .cloned()
+            .unwrap_or_default();
+        let full_html = build_full_html(
+            &app_state.file_name,
+            &html_content,
+            &app_state.css_content,
+            &app_state.fonts,
+            true, // Enable live reload script
+            "",
+            &app_state.file_name,
+            app_state.custom_template.as_deref(),
+        );
+        Ok(warp::reply::html(full_html))
+    }
+}
+
+async fn serve_view(
+    tail: warp::path::Tail,
+    range_header: Option<String>,
+    app_state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let decoded_tail = percent_decode_str(tail.as_str()).decode_utf8_lossy().into_owned();
+    let rel_path = PathBuf::from(decoded_tail);
+    let abs_path = app_state.root_path.join(&rel_path);
+
+    if !is_markdown_file(&rel_path) {
+        return read_ranged_bytes(&abs_path, &app_state.root_path, range_header).await;
+    }
+
+    if canonicalize_within_root(&app_state.root_path, &abs_path).is_none() {
+        return Err(warp::reject::not_found());
+    }
+
+    let cached = app_state.html_cache.read().await.get(&rel_path).cloned();
+    let html_output = match cached {
+        Some(html) => html,
+        None => {
+            let markdown_input =
+                std::fs::read_to_string(&abs_path).map_err(|_| warp::reject::not_found())?;
+            let html_output = render_markdown_to_html(&markdown_input, &app_state.highlight_theme);
+            app_state
+                .html_cache
+                .write()
+                .await
+                .insert(rel_path.clone(), html_output.clone());
+            html_output
+        }
+    };
+
+    let files = app_state.files.read().await;
+    let sidebar = build_sidebar_html(&files, Some(rel_path.as_path()));
+    let rel_path_str = rel_path.to_string_lossy().to_string();
     let full_html = build_full_html(
-        &app_state.file_name,
-        &html_content,
+        &rel_path_str,
+        &html_output,
         &app_state.css_content,
         &app_state.fonts,
-        true, // Enable live reload script
+        true,
+        &sidebar,
+        &rel_path_str,
+        app_state.custom_template.as_deref(),
     );
-    Ok(warp::reply::html(full_html))
+    Ok(warp::reply::html(full_html).into_response())
+}
+
+async fn serve_asset_fallback(
+    full_path: warp::path::FullPath,
+    range_header: Option<String>,
+    app_state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let decoded_path = percent_decode_str(full_path.as_str().trim_start_matches('/'))
+        .decode_utf8_lossy()
+        .into_owned();
+    let rel_path = PathBuf::from(decoded_path);
+    let abs_path = app_state.root_path.join(&rel_path);
+    read_ranged_bytes(&abs_path, &app_state.root_path, range_header).await
+}
+
+// Resolves `abs_path` against `root_path`, rejecting anything that canonicalizes to
+// outside of it (a `../` escape or a symlink pointing off-root) and anything that
+// doesn't exist.
+fn canonicalize_within_root(root_path: &Path, abs_path: &Path) -> Option<PathBuf> {
+    let canonical_root = root_path.canonicalize().ok()?;
+    let canonical_abs = abs_path.canonicalize().ok()?;
+    if canonical_abs.starts_with(&canonical_root) {
+        Some(canonical_abs)
+    } else {
+        None
+    }
+}
+
+fn parse_range_header(value: &str) -> Option<(u64, Option<u64>)> {
+    let spec = value.strip_prefix("bytes=")?;
+    let (start_str, end_str) = spec.split_once('-')?;
+    let start: u64 = start_str.trim().parse().ok()?;
+    let end = if end_str.trim().is_empty() {
+        None
+    } else {
+        end_str.trim().parse().ok()
+    };
+    Some((start, end))
+}
+
+// Serves `abs_path` relative to `root_path` (rejecting traversal outside it), honoring a
+// `Range: bytes=start-end` header with 206/416 responses so browsers can seek large media.
+async fn read_ranged_bytes(
+    abs_path: &Path,
+    root_path: &Path,
+    range_header: Option<String>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    if canonicalize_within_root(root_path, abs_path).is_none() {
+        return Err(warp::reject::not_found());
+    }
+
+    let bytes = std::fs::read(abs_path).map_err(|_| warp::reject::not_found())?;
+    let total = bytes.len() as u64;
+    let ext = abs_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let content_type = mime_type_for_extension(ext);
+
+    let range = range_header.as_deref().and_then(parse_range_header);
+
+    if let Some((start, end_opt)) = range {
+        let malformed = start >= total || end_opt.is_some_and(|end| end < start);
+        if malformed {
+            let response = warp::http::Response::builder()
+                .status(warp::http::StatusCode::RANGE_NOT_SATISFIABLE)
+                .header("Content-Range", format!("bytes */{}", total))
+                .body(warp::hyper::Body::empty())
+                .map_err(|_| warp::reject::not_found())?;
+            return Ok(response);
+        }
+    }
+
+    let (status, body, content_range) = match range {
+        Some((start, end_opt)) => {
+            let end = end_opt.unwrap_or(total - 1).min(total - 1);
+            let slice = bytes[start as usize..=end as usize].to_vec();
+            (
+                warp::http::StatusCode::PARTIAL_CONTENT,
+                slice,
+                Some(format!("bytes {}-{}/{}", start, end, total)),
+            )
+        }
+        None => (warp::http::StatusCode::OK, bytes, None),
+    };
+
+    let mut builder = warp::http::Response::builder()
+        .status(status)
+        .header("Content-Type", content_type)
+        .header("Accept-Ranges", "bytes")
+        .header("Content-Length", body.len().to_string());
+
+    if let Some(content_range) = content_range {
+        builder = builder.header("Content-Range", content_range);
+    }
+
+    builder
+        .body(warp::hyper::Body::from(body))
+        .map_err(|_| warp::reject::not_found())
+}
+
+fn is_markdown_file(path: &Path) -> bool {
+    matches!(
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref(),
+        Some("md") | Some("markdown")
+    )
+}
+
+fn build_file_index(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_markdown_files(root, root, &mut files);
+    files.sort();
+    files
+}
+
+fn collect_markdown_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_markdown_files(root, &path, files);
+        } else if is_markdown_file(&path) {
+            if let Ok(rel_path) = path.strip_prefix(root) {
+                files.push(rel_path.to_path_buf());
+            }
+        }
+    }
+}
+
+fn icon_for_extension(ext: &str) -> &'static str {
+    match ext.to_ascii_lowercase().as_str() {
+        "md" | "markdown" => "📄",
+        _ => "📁",
+    }
+}
+
+// Renders the file-tree sidebar used by directory/project mode; `current` highlights
+// whichever file the client has open so the active entry stands out in the list.
+fn build_sidebar_html(files: &[PathBuf], current: Option<&Path>) -> String {
+    let mut items = String::new();
+    for rel_path in files {
+        let display = rel_path.to_string_lossy().replace('\\', "/");
+        let ext = rel_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let icon = icon_for_extension(ext);
+        let is_active = current == Some(rel_path.as_path());
+        items.push_str(&format!(
+            "<li><a href=\"/view/{href}\"{class}>{icon} {name}</a></li>\n",
+            href = percent_encode_path(&display),
+            class = if is_active { " class=\"active\"" } else { "" },
+            icon = icon,
+            name = html_escape(&display),
+        ));
+    }
+    format!("<nav id=\"sidebar\"><ul>{}</ul></nav>", items)
+}
+
+// Percent-encodes characters that would otherwise break out of the `href="..."` /
+// `"/events?path=..."` string literals this feeds into, not just ones that are
+// meaningful in a URL - a filename can legally contain `"` or `<` on Linux.
+fn percent_encode_path(path: &str) -> String {
+    path.chars()
+        .map(|c| match c {
+            ' ' => "%20".to_string(),
+            '#' => "%23".to_string(),
+            '?' => "%3F".to_string(),
+            '"' => "%22".to_string(),
+            '\'' => "%27".to_string(),
+            '<' => "%3C".to_string(),
+            '>' => "%3E".to_string(),
+            '&' => "%26".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn html_escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
 fn build_full_html(
@@ -389,22 +962,42 @@ fn build_full_html(
     style: &str,
     fonts: &Fonts,
     enable_reload: bool,
+    sidebar: &str,
+    reload_path: &str,
+    template: Option<&str>,
 ) -> String {
     let reload_script = if enable_reload {
-        r#"
+        format!(
+            r#"
         <script>
-            var evtSource = new EventSource("/events");
-            evtSource.onmessage = function(e) {
-                if (e.data === "reload") {
+            var evtSource = new EventSource("/events?path={}");
+            evtSource.onmessage = function(e) {{
+                if (e.data === "reload") {{
                     location.reload();
-                }
-            };
+                }}
+            }};
         </script>
-        "#
+        "#,
+            percent_encode_path(reload_path)
+        )
     } else {
-        ""
+        String::new()
     };
 
+    if let Some(template) = template {
+        let content = format!("{}<main id=\"content\">{}</main>", sidebar, html_output);
+        let styles = build_styles_block(style, fonts);
+        return render_template(
+            template,
+            &[
+                ("title", file_name),
+                ("content", &content),
+                ("styles", &styles),
+                ("reload_script", &reload_script),
+            ],
+        );
+    }
+
     format!(
         r#"
 <!DOCTYPE html>
@@ -459,6 +1052,9 @@ fn build_full_html(
 </head>
 <body>
     {}
+    <main id="content">
+    {}
+    </main>
     {}
 </body>
 </html>
@@ -469,7 +1065,391 @@ fn build_full_html(
         fonts.font_light,
         style,
         file_name,
+        sidebar,
         html_output,
         reload_script
     )
 }
+
+// Combines the favicon, the Oswald @font-face declarations and the page stylesheet
+// into the single block a custom `--template`'s {{ styles }} placeholder gets.
+fn build_styles_block(style: &str, fonts: &Fonts) -> String {
+    format!(
+        r#"<link rel="icon" href="data:image/x-icon;base64,{}">
+<style>
+    @font-face {{
+        font-family: 'Oswald';
+        src: url(data:font/truetype;charset=utf-8;base64,{}) format('truetype');
+        font-weight: 400;
+        font-style: normal;
+    }}
+    @font-face {{
+        font-family: 'Oswald';
+        src: url(data:font/truetype;charset=utf-8;base64,{}) format('truetype');
+        font-weight: 700;
+        font-style: normal;
+    }}
+    @font-face {{
+        font-family: 'Oswald';
+        src: url(data:font/truetype;charset=utf-8;base64,{}) format('truetype');
+        font-weight: 300;
+        font-style: normal;
+    }}
+    {}
+</style>"#,
+        fonts.favicon, fonts.font_regular, fonts.font_medium, fonts.font_light, style
+    )
+}
+
+// Walks the rendered HTML and rewrites `src`/`href` on <img>, <link rel="stylesheet">,
+// <script> and <a> into `data:` URIs so the page no longer depends on files next to it.
+fn embed_assets(html: &str, base_dir: &Path, allow_fetch: bool) -> String {
+    let mut visited = HashSet::new();
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(lt_idx) = rest.find('<') {
+        output.push_str(&rest[..lt_idx]);
+        rest = &rest[lt_idx..];
+        let gt_idx = match rest.find('>') {
+            Some(idx) => idx,
+            None => {
+                output.push_str(rest);
+                rest = "";
+                break;
+            }
+        };
+        let tag = &rest[..=gt_idx];
+        output.push_str(&embed_tag(tag, base_dir, allow_fetch, &mut visited));
+        rest = &rest[gt_idx + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+fn embed_tag(tag: &str, base_dir: &Path, allow_fetch: bool, visited: &mut HashSet<String>) -> String {
+    let name = tag
+        .trim_start_matches('<')
+        .trim_start_matches('/')
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    let attr = match name.as_str() {
+        "img" | "script" => "src",
+        "link" if tag.contains("stylesheet") => "href",
+        "a" => "href",
+        _ => return tag.to_string(),
+    };
+
+    inline_attr(tag, attr, base_dir, allow_fetch, visited).unwrap_or_else(|| tag.to_string())
+}
+
+fn inline_attr(
+    tag: &str,
+    attr: &str,
+    base_dir: &Path,
+    allow_fetch: bool,
+    visited: &mut HashSet<String>,
+) -> Option<String> {
+    let attr_pat = format!("{}=\"", attr);
+    let start = tag.find(&attr_pat)? + attr_pat.len();
+    let end = start + tag[start..].find('"')?;
+    let value = &tag[start..end];
+
+    if value.is_empty() || value.starts_with("data:") || value.starts_with('#') {
+        return None;
+    }
+
+    let data_uri = if value.starts_with("http://") || value.starts_with("https://") {
+        if !allow_fetch {
+            return None;
+        }
+        fetch_and_encode(value, visited)?
+    } else {
+        read_and_encode(value, base_dir, visited)?
+    };
+
+    let mut new_tag = String::with_capacity(tag.len() + data_uri.len());
+    new_tag.push_str(&tag[..start]);
+    new_tag.push_str(&data_uri);
+    new_tag.push_str(&tag[end..]);
+    Some(new_tag)
+}
+
+// rel_path comes straight from an <img src>/<link href> attribute in the document being
+// embedded, so it can be absolute (`/etc/passwd`) or a `../` escape - canonicalize_within_root
+// rejects anything that doesn't resolve inside base_dir before we read and base64 it.
+fn read_and_encode(rel_path: &str, base_dir: &Path, visited: &mut HashSet<String>) -> Option<String> {
+    let path = base_dir.join(rel_path);
+    let canonical = canonicalize_within_root(base_dir, &path)?;
+    if !visited.insert(canonical.to_string_lossy().to_string()) {
+        return None;
+    }
+
+    let ext = canonical.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let mime = mime_type_for_extension(ext);
+
+    if mime == "text/css" {
+        let css = std::fs::read_to_string(&canonical).ok()?;
+        let css_dir = canonical.parent().unwrap_or(base_dir);
+        let inlined_css = inline_css_imports(&css, css_dir, base_dir, visited);
+        Some(format!("data:text/css;base64,{}", encode(inlined_css.as_bytes())))
+    } else {
+        let bytes = std::fs::read(&canonical).ok()?;
+        Some(format!("data:{};base64,{}", mime, encode(&bytes)))
+    }
+}
+
+fn fetch_and_encode(url: &str, visited: &mut HashSet<String>) -> Option<String> {
+    if !visited.insert(url.to_string()) {
+        return None;
+    }
+
+    let response = reqwest::blocking::get(url).ok()?;
+    let mime = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.split(';').next().unwrap_or(s).to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let bytes = response.bytes().ok()?;
+    Some(format!("data:{};base64,{}", mime, encode(&bytes)))
+}
+
+// Recursively inlines `@import` statements so a themed stylesheet built from several
+// files still ends up as one self-contained data URI; `visited` breaks import cycles.
+// `root_dir` is the original embed base_dir (not the current file's directory, which
+// moves as we descend into subdirectories) - every import must stay within it.
+fn inline_css_imports(css: &str, css_dir: &Path, root_dir: &Path, visited: &mut HashSet<String>) -> String {
+    let mut output = String::with_capacity(css.len());
+    let mut rest = css;
+
+    while let Some(idx) = rest.find("@import") {
+        output.push_str(&rest[..idx]);
+        let after = &rest[idx..];
+        let stmt_end = after.find(';').map(|i| i + 1).unwrap_or(after.len());
+        let stmt = &after[..stmt_end];
+
+        match parse_css_import_path(stmt) {
+            Some(import_path) => {
+                let import_file = css_dir.join(&import_path);
+                match canonicalize_within_root(root_dir, &import_file) {
+                    Some(canonical) => {
+                        if visited.insert(canonical.to_string_lossy().to_string()) {
+                            if let Ok(imported_css) = std::fs::read_to_string(&canonical) {
+                                let imported_dir = canonical.parent().unwrap_or(css_dir);
+                                output.push_str(&inline_css_imports(
+                                    &imported_css,
+                                    imported_dir,
+                                    root_dir,
+                                    visited,
+                                ));
+                            }
+                        }
+                        // already visited: drop the @import to break the cycle
+                    }
+                    None => output.push_str(stmt),
+                }
+            }
+            None => output.push_str(stmt),
+        }
+
+        rest = &after[stmt_end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn parse_css_import_path(stmt: &str) -> Option<String> {
+    let inner = if let Some(url_idx) = stmt.find("url(") {
+        let after = &stmt[url_idx + 4..];
+        let end = after.find(')')?;
+        after[..end].trim().trim_matches(|c| c == '"' || c == '\'').to_string()
+    } else {
+        let start = stmt.find(['"', '\''])?;
+        let quote = stmt.as_bytes()[start] as char;
+        let rest = &stmt[start + 1..];
+        let end = rest.find(quote)?;
+        rest[..end].to_string()
+    };
+
+    if inner.starts_with("http://") || inner.starts_with("https://") {
+        None
+    } else {
+        Some(inner)
+    }
+}
+
+fn mime_type_for_extension(ext: &str) -> &'static str {
+    match ext.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "css" => "text/css",
+        "js" => "application/javascript",
+        "json" => "application/json",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_encode_path_escapes_attribute_breakout_characters() {
+        let encoded = percent_encode_path(r#"x" onmouseover="alert(1)"#);
+        assert!(!encoded.contains('"'));
+        assert_eq!(encoded, "x%22%20onmouseover=%22alert(1)");
+    }
+
+    #[test]
+    fn parse_range_header_parses_bounded_range() {
+        assert_eq!(parse_range_header("bytes=0-499"), Some((0, Some(499))));
+    }
+
+    #[test]
+    fn parse_range_header_parses_open_ended_range() {
+        assert_eq!(parse_range_header("bytes=500-"), Some((500, None)));
+    }
+
+    #[test]
+    fn parse_range_header_rejects_malformed_values() {
+        assert_eq!(parse_range_header("bytes=abc-def"), None);
+        assert_eq!(parse_range_header("bytes=500"), None);
+        assert_eq!(parse_range_header("500-600"), None);
+    }
+
+    #[test]
+    fn render_template_substitutes_spaced_and_unspaced_placeholders() {
+        let output = render_template(
+            "<title>{{ title }}</title>{{body}}",
+            &[("title", "Doc"), ("body", "<p>hi</p>")],
+        );
+        assert_eq!(output, "<title>Doc</title><p>hi</p>");
+    }
+
+    #[test]
+    fn render_template_leaves_unknown_placeholders_untouched() {
+        let output = render_template("{{ known }} {{ unknown }}", &[("known", "x")]);
+        assert_eq!(output, "x {{ unknown }}");
+    }
+
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("omd-test-{}-{}", label, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn inline_attr_embeds_local_file_as_data_uri() {
+        let dir = scratch_dir("inline-attr");
+        std::fs::write(dir.join("pixel.png"), b"\x89PNG\r\n").unwrap();
+
+        let mut visited = HashSet::new();
+        let result = inline_attr(r#"<img src="pixel.png">"#, "src", &dir, false, &mut visited).unwrap();
+
+        assert!(result.starts_with(r#"<img src="data:image/png;base64,"#));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn inline_attr_skips_data_uris_fragments_and_remote_without_fetch() {
+        let dir = scratch_dir("inline-attr-skips");
+        let mut visited = HashSet::new();
+
+        assert!(inline_attr(
+            r#"<img src="data:image/png;base64,AA==">"#,
+            "src",
+            &dir,
+            false,
+            &mut visited
+        )
+        .is_none());
+        assert!(inline_attr(r#"<a href="#section">"#, "href", &dir, false, &mut visited).is_none());
+        assert!(inline_attr(
+            r#"<img src="https://example.com/a.png">"#,
+            "src",
+            &dir,
+            false,
+            &mut visited
+        )
+        .is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn inline_attr_rejects_src_outside_base_dir() {
+        let base = scratch_dir("inline-attr-traversal");
+        let dir = base.join("doc");
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret.png"), b"\x89PNG\r\n").unwrap();
+
+        let mut visited = HashSet::new();
+        assert!(inline_attr(
+            r#"<img src="../outside/secret.png">"#,
+            "src",
+            &dir,
+            false,
+            &mut visited
+        )
+        .is_none());
+        assert!(inline_attr(
+            &format!(r#"<img src="{}">"#, outside.join("secret.png").display()),
+            "src",
+            &dir,
+            false,
+            &mut visited
+        )
+        .is_none());
+
+        std::fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn embed_assets_inlines_matching_tags_and_leaves_others_alone() {
+        let dir = scratch_dir("embed-assets");
+        std::fs::write(dir.join("pixel.png"), b"\x89PNG\r\n").unwrap();
+
+        let html = r#"<p>hi</p><img src="pixel.png"><a href="https://example.com">link</a>"#;
+        let output = embed_assets(html, &dir, false);
+
+        assert!(output.contains(r#"<img src="data:image/png;base64,"#));
+        assert!(output.contains(r#"<a href="https://example.com">link</a>"#));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn canonicalize_within_root_rejects_traversal_outside_root() {
+        let base = scratch_dir("traversal");
+        let root = base.join("root");
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret.md"), "top secret").unwrap();
+        std::fs::write(root.join("doc.md"), "hello").unwrap();
+
+        let escaping = root.join("..").join("outside").join("secret.md");
+        assert!(canonicalize_within_root(&root, &escaping).is_none());
+        assert!(canonicalize_within_root(&root, &root.join("doc.md")).is_some());
+
+        std::fs::remove_dir_all(&base).ok();
+    }
+}